@@ -1,16 +1,73 @@
 use axum::{
     Router,
+    body::Body,
     extract::{Path, Request, State},
-    http::{HeaderValue, StatusCode, header},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
     middleware::{Next, from_fn_with_state},
     response::{IntoResponse, Json, Response},
-    routing::get,
+    routing::{any, get, post},
 };
 use serde::{Serialize, Deserialize};
 use clap::Parser;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use reqwest::Client;
 use tokio::signal;
+use axum_server::tls_rustls::RustlsConfig;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use arc_swap::ArcSwap;
+use instant_acme::{
+    Account, AuthorizationStatus, ChallengeType, Identifier, NewAccount, NewOrder, OrderStatus,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use rand::Rng;
+use rand::distributions::Alphanumeric;
+
+// Renew well ahead of the ~90 day lifetime Let's Encrypt certs carry.
+const ACME_RENEWAL_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24 * 60);
+// Retry sooner on a failed renewal attempt rather than waiting out the full interval, so a
+// transient failure can't let the certificate silently expire.
+const ACME_RENEWAL_RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Caps how many tokens `/generate_token` can mint, since each caller is already authenticated
+// but an unbounded set would still let a compromised credential grow memory without limit.
+const MAX_GENERATED_TOKENS: usize = 10_000;
+
+// Request/response headers that are specific to a single hop and must not be forwarded
+// verbatim between the client and the backend.
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+fn is_hop_by_hop(name: &HeaderName) -> bool {
+    HOP_BY_HOP_HEADERS.iter().any(|h| name.as_str().eq_ignore_ascii_case(h))
+}
+
+// Decides whether an inbound request header should be copied to the backend unmodified.
+// Always drops hop-by-hop headers, Host (reqwest sets it from the backend URL), the client's
+// own Authorization (the inbound credential client_auth already checked, never the upstream
+// secret), and whichever header `auth_header_name` names - the upstream secret gets injected
+// there explicitly, under the backend's own value, right after this loop runs.
+fn should_forward_header(name: &HeaderName, auth_header_name: &str) -> bool {
+    !is_hop_by_hop(name)
+        && name != header::HOST
+        && name != header::AUTHORIZATION
+        && !name.as_str().eq_ignore_ascii_case(auth_header_name)
+}
+
+// `path` comes from the `{*path}` wildcard capture, which never includes a leading slash, so
+// tolerate prefixes written either as "api" or "/api" in the config.
+fn match_route<'a>(routes: &'a [RouteConfig], path: &str) -> Option<&'a RouteConfig> {
+    routes.iter().find(|r| path.starts_with(r.prefix.trim_start_matches('/')))
+}
 
 #[derive(Serialize)]
 struct ApiResponse {
@@ -25,6 +82,12 @@ struct Config {
     secret_token: String,
     port: u16,
     extra_values: Option<String>,
+    tls_cert_path: Option<String>,
+    tls_key_path: Option<String>,
+    metrics_port: u16,
+    acme: AcmeConfig,
+    routes: Vec<RouteConfig>,
+    client_tokens: HashSet<String>,
 }
 
 // Implementation of the default values - serde(default) ensures that the default values are taken from here
@@ -35,12 +98,60 @@ impl Default for Config {
             secret_token: "my-secret-token".to_string(),
             port: 3000,
             extra_values: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            metrics_port: 9090,
+            acme: AcmeConfig::default(),
+            routes: Vec::new(),
+            client_tokens: HashSet::new(),
+        }
+    }
+}
+
+// A single entry in the routing table: requests whose path starts with `prefix` are sent to
+// `backend_url` with `secret_token` attached via `header_name` (or `Authorization: Bearer` when
+// `header_name` is unset), instead of the default backend/token pair.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, default)]
+struct RouteConfig {
+    prefix: String,
+    backend_url: String,
+    secret_token: String,
+    header_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields, default)]
+struct AcmeConfig {
+    enabled: bool,
+    directory_url: String,
+    contact_email: String,
+    domains: Vec<String>,
+    // Directory where the ACME account credentials and the issued cert/key are cached across
+    // restarts, so a restart doesn't re-register a new account or re-issue from scratch.
+    cache_dir: String,
+}
+
+impl Default for AcmeConfig {
+    fn default() -> Self {
+        AcmeConfig {
+            enabled: false,
+            directory_url: "https://acme-v02.api.letsencrypt.org/directory".to_string(),
+            contact_email: String::new(),
+            domains: Vec::new(),
+            cache_dir: "acme-cache".to_string(),
         }
     }
 }
+
 struct AppState {
-    config: Config,
+    config: ArcSwap<Config>,
     http_client: reqwest::Client,
+    metrics_handle: PrometheusHandle,
+    acme_challenges: Mutex<HashMap<String, String>>,
+    // Tokens minted by `/generate_token`. Kept separate from `config.client_tokens` since they're
+    // provisioned at runtime rather than through the config file, and don't survive a restart.
+    generated_tokens: Mutex<HashSet<String>>,
 }
 
 #[derive(Parser, Debug)]
@@ -56,6 +167,221 @@ async fn shutdown_signal() {
     println!("Shutdown signal received, starting graceful shutdown...");
 }
 
+// Waits for Ctrl+C, then tells an `axum_server::Handle` to start draining connections.
+async fn shutdown_handle(handle: axum_server::Handle) {
+    shutdown_signal().await;
+    handle.graceful_shutdown(Some(Duration::from_secs(30)));
+}
+
+fn load_config(path: &str) -> Result<Config, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read config file '{}': {}", path, e))?;
+    serde_yaml::from_str(&contents)
+        .map_err(|e| format!("Failed to parse config file '{}': {}", path, e))
+}
+
+fn acme_account_path(cache_dir: &str) -> std::path::PathBuf {
+    std::path::Path::new(cache_dir).join("account.json")
+}
+
+fn acme_cert_path(cache_dir: &str) -> std::path::PathBuf {
+    std::path::Path::new(cache_dir).join("cert.pem")
+}
+
+fn acme_key_path(cache_dir: &str) -> std::path::PathBuf {
+    std::path::Path::new(cache_dir).join("key.pem")
+}
+
+// Records when the cached cert/key pair was issued, so a restart can schedule its first renewal
+// relative to actual issuance instead of relative to process start.
+fn acme_issued_at_path(cache_dir: &str) -> std::path::PathBuf {
+    std::path::Path::new(cache_dir).join("issued_at")
+}
+
+// Returns the cached cert/key pair if both files are present, so a restart can start serving
+// immediately instead of re-issuing before the renewal loop is due to run.
+fn load_cached_acme_certificate(cache_dir: &str) -> Option<(String, String)> {
+    let cert_pem = std::fs::read_to_string(acme_cert_path(cache_dir)).ok()?;
+    let key_pem = std::fs::read_to_string(acme_key_path(cache_dir)).ok()?;
+    Some((cert_pem, key_pem))
+}
+
+// Returns when the cached cert was issued, if that's recorded. Absent for caches written before
+// this field existed, in which case the caller should treat the cert's age as unknown.
+fn load_cached_acme_issued_at(cache_dir: &str) -> Option<SystemTime> {
+    let raw = std::fs::read_to_string(acme_issued_at_path(cache_dir)).ok()?;
+    let secs: u64 = raw.trim().parse().ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+fn save_cached_acme_certificate(cache_dir: &str, cert_pem: &str, key_pem: &str) -> Result<(), String> {
+    std::fs::create_dir_all(cache_dir)
+        .map_err(|e| format!("Failed to create ACME cache dir '{}': {}", cache_dir, e))?;
+    std::fs::write(acme_cert_path(cache_dir), cert_pem)
+        .map_err(|e| format!("Failed to cache ACME certificate: {}", e))?;
+    std::fs::write(acme_key_path(cache_dir), key_pem)
+        .map_err(|e| format!("Failed to cache ACME key: {}", e))?;
+    let issued_at = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| format!("System clock is before the Unix epoch: {}", e))?
+        .as_secs();
+    std::fs::write(acme_issued_at_path(cache_dir), issued_at.to_string())
+        .map_err(|e| format!("Failed to record ACME issuance time: {}", e))?;
+    Ok(())
+}
+
+// How long to wait before the next renewal attempt, given when the current cert was issued.
+// Unknown issuance age (no cache, or a cache written before this field existed) is treated as
+// due for renewal immediately, since we can't otherwise tell how close it is to expiring.
+fn acme_renewal_delay(issued_at: Option<SystemTime>) -> Duration {
+    match issued_at {
+        Some(issued_at) => {
+            let age = SystemTime::now().duration_since(issued_at).unwrap_or(Duration::ZERO);
+            ACME_RENEWAL_INTERVAL.saturating_sub(age)
+        }
+        None => Duration::ZERO,
+    }
+}
+
+// Runs the ACME HTTP-01 flow end to end: reuse a cached account (or register and cache a new
+// one), order the configured domains, answer the challenges via `acme_challenges` (served by the
+// `acme_challenge` handler), and return the issued certificate and key as PEM once the order
+// finalizes, caching them to disk so a restart doesn't re-issue from scratch.
+async fn obtain_acme_certificate(
+    acme: &AcmeConfig,
+    challenges: &Mutex<HashMap<String, String>>,
+) -> Result<(String, String), String> {
+    let account = match std::fs::read_to_string(acme_account_path(&acme.cache_dir)).ok() {
+        Some(saved) => {
+            let credentials = serde_json::from_str(&saved)
+                .map_err(|e| format!("Failed to parse cached ACME account: {}", e))?;
+            Account::from_credentials(credentials)
+                .await
+                .map_err(|e| format!("Failed to reuse cached ACME account: {}", e))?
+        }
+        None => {
+            let (account, credentials) = Account::create(
+                &NewAccount {
+                    contact: &[&format!("mailto:{}", acme.contact_email)],
+                    terms_of_service_agreed: true,
+                    only_return_existing: false,
+                },
+                &acme.directory_url,
+                None,
+            )
+            .await
+            .map_err(|e| format!("Failed to create ACME account: {}", e))?;
+
+            std::fs::create_dir_all(&acme.cache_dir)
+                .map_err(|e| format!("Failed to create ACME cache dir '{}': {}", acme.cache_dir, e))?;
+            let serialized = serde_json::to_string(&credentials)
+                .map_err(|e| format!("Failed to serialize ACME account: {}", e))?;
+            std::fs::write(acme_account_path(&acme.cache_dir), serialized)
+                .map_err(|e| format!("Failed to cache ACME account: {}", e))?;
+
+            account
+        }
+    };
+
+    let identifiers: Vec<Identifier> = acme.domains.iter().cloned().map(Identifier::Dns).collect();
+    let mut order = account
+        .new_order(&NewOrder { identifiers: &identifiers })
+        .await
+        .map_err(|e| format!("Failed to create ACME order: {}", e))?;
+
+    let authorizations = order
+        .authorizations()
+        .await
+        .map_err(|e| format!("Failed to fetch ACME authorizations: {}", e))?;
+
+    for authz in &authorizations {
+        if authz.status == AuthorizationStatus::Valid {
+            continue;
+        }
+        let challenge = authz
+            .challenges
+            .iter()
+            .find(|c| c.r#type == ChallengeType::Http01)
+            .ok_or_else(|| "Backend did not offer an HTTP-01 challenge".to_string())?;
+
+        let key_auth = order.key_authorization(challenge).as_str().to_string();
+        challenges.lock().unwrap().insert(challenge.token.clone(), key_auth);
+
+        order
+            .set_challenge_ready(&challenge.url)
+            .await
+            .map_err(|e| format!("Failed to mark ACME challenge ready: {}", e))?;
+    }
+
+    // Poll until the CA has validated every challenge (or given up).
+    for _ in 0..10 {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let state = order
+            .refresh()
+            .await
+            .map_err(|e| format!("Failed to refresh ACME order: {}", e))?;
+        match state.status {
+            OrderStatus::Ready | OrderStatus::Valid => break,
+            OrderStatus::Invalid => return Err("ACME order became invalid".to_string()),
+            _ => continue,
+        }
+    }
+
+    let mut params = rcgen::CertificateParams::new(acme.domains.clone())
+        .map_err(|e| format!("Failed to build certificate params: {}", e))?;
+    params.distinguished_name = rcgen::DistinguishedName::new();
+    let private_key = rcgen::KeyPair::generate()
+        .map_err(|e| format!("Failed to generate certificate key: {}", e))?;
+    let csr = params
+        .serialize_request(&private_key)
+        .map_err(|e| format!("Failed to build CSR: {}", e))?;
+    let private_key_pem = private_key.serialize_pem();
+
+    order
+        .finalize(csr.der())
+        .await
+        .map_err(|e| format!("Failed to finalize ACME order: {}", e))?;
+
+    for _ in 0..10 {
+        if let Some(cert_chain_pem) = order
+            .certificate()
+            .await
+            .map_err(|e| format!("Failed to download ACME certificate: {}", e))?
+        {
+            save_cached_acme_certificate(&acme.cache_dir, &cert_chain_pem, &private_key_pem)?;
+            return Ok((cert_chain_pem, private_key_pem));
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+
+    Err("Timed out waiting for ACME certificate".to_string())
+}
+
+// Listens for SIGHUP and atomically swaps in the re-parsed config. A parse error just logs
+// and leaves the previously-loaded config in place so a typo'd reload can't take the proxy down.
+async fn reload_on_sighup(path: String, config: Arc<AppState>) {
+    let mut sighup = match signal::unix::signal(signal::unix::SignalKind::hangup()) {
+        Ok(sighup) => sighup,
+        Err(e) => {
+            eprintln!("Failed to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+
+    loop {
+        sighup.recv().await;
+        match load_config(&path) {
+            Ok(new_config) => {
+                println!("Reloaded config from '{}'", path);
+                config.config.store(Arc::new(new_config));
+            }
+            Err(e) => {
+                eprintln!("Config reload failed, keeping previous config: {}", e);
+            }
+        }
+    }
+}
+
 
 #[tokio::main]
 async fn main() {
@@ -69,43 +395,156 @@ async fn main() {
     };
 
     // Deserialize config_file - current file name only as it is in header of file
-    let server_config_contents = match std::fs::read_to_string(&args.config) {
-        Ok(contents) => contents,
-        Err(e) => {
-            eprintln!("Failed to read config file '{}': {}", args.config, e);
-            std::process::exit(1);
-        }
-    };
-
-    let server_config: Config = match serde_yaml::from_str(&server_config_contents) {
+    let server_config = match load_config(&args.config) {
         Ok(config) => config,
         Err(e) => {
-            eprintln!("Failed to parse file '{}': {}", {args.config}, e);
+            eprintln!("{}", e);
             std::process::exit(1);
         }
     };
 
-    println!("{}", &server_config_contents);
+    // Log what loaded without a full Debug dump - Config carries every route's secret_token and
+    // the whole client_tokens set, which would otherwise land in plaintext in the startup log on
+    // every boot.
+    println!(
+        "Loaded config: backend_url={}, port={}, metrics_port={}, acme_enabled={}, tls_configured={}, routes={}, client_tokens={}",
+        server_config.backend_url,
+        server_config.port,
+        server_config.metrics_port,
+        server_config.acme.enabled,
+        server_config.tls_cert_path.is_some() && server_config.tls_key_path.is_some(),
+        server_config.routes.len(),
+        server_config.client_tokens.len(),
+    );
+
+    let metrics_handle = PrometheusBuilder::new()
+        .install_recorder()
+        .expect("Failed to install Prometheus recorder");
+
     // Wrapped server_config which is why no reference used.
     let shared_config = Arc::new(AppState {
-        config: server_config,
+        config: ArcSwap::from_pointee(server_config),
         http_client: Client::new(),
+        metrics_handle,
+        acme_challenges: Mutex::new(HashMap::new()),
+        generated_tokens: Mutex::new(HashSet::new()),
     });
 
+    tokio::spawn(reload_on_sighup(args.config.clone(), shared_config.clone()));
+
+    // Minting tokens is just as sensitive as proxying, so it sits behind the same `client_auth`
+    // credential check; only health checks, config introspection, and ACME challenges stay open.
+    let protected = Router::new()
+        .route("/{*path}", any(proxy))
+        .route("/generate_token", post(generate_token))
+        .layer(from_fn_with_state(shared_config.clone(), client_auth));
+
     // app with routes and fallback
     let app = Router::new()
         .route("/health", get(health))
         .route("/config", get(config))
-        .route("/{*path}", get(proxy))
-        .layer(from_fn_with_state(shared_config.clone(), my_middleware))
+        .route("/.well-known/acme-challenge/{token}", get(acme_challenge))
+        .merge(protected)
         .with_state(shared_config.clone());
 
-    // run app with hyper, listening on port suggested by the CLI
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", shared_config.config.port)).await.unwrap();
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .unwrap();
+    // Metrics live on their own listener so operators can keep /metrics off the public interface.
+    let metrics_app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(shared_config.clone());
+    let metrics_addr: std::net::SocketAddr = format!("0.0.0.0:{}", shared_config.config.load().metrics_port).parse().unwrap();
+    let metrics_listener = tokio::net::TcpListener::bind(metrics_addr).await.unwrap();
+    tokio::spawn(async move {
+        axum::serve(metrics_listener, metrics_app)
+            .with_graceful_shutdown(shutdown_signal())
+            .await
+            .unwrap();
+    });
+
+    let startup_config = shared_config.config.load_full();
+    let addr = format!("0.0.0.0:{}", startup_config.port).parse().unwrap();
+
+    // Serve over TLS when ACME is enabled or a static cert/key pair is configured, otherwise
+    // fall back to plaintext so existing deployments keep working unchanged.
+    if startup_config.acme.enabled {
+        // Reuse a cached cert from a previous run if one exists, so a restart serves immediately
+        // instead of re-issuing before the renewal loop is next due to run.
+        let (cert_pem, key_pem) = match load_cached_acme_certificate(&startup_config.acme.cache_dir) {
+            Some(cached) => cached,
+            None => obtain_acme_certificate(&startup_config.acme, &shared_config.acme_challenges)
+                .await
+                .expect("Failed to obtain ACME certificate"),
+        };
+        let tls_config = RustlsConfig::from_pem(cert_pem.into_bytes(), key_pem.into_bytes())
+            .await
+            .expect("Failed to load ACME certificate");
+
+        // Renew in the background and hot-swap the cert into the already-running listener. The
+        // first wait is relative to when this cert was actually issued (not to process start),
+        // so restarting shortly before expiry doesn't grant another full interval before the
+        // next renewal attempt. A failed attempt retries after a short backoff instead of waiting
+        // out the full interval, so a transient failure can't leave an expired cert in place.
+        let mut renewal_delay = acme_renewal_delay(load_cached_acme_issued_at(&startup_config.acme.cache_dir));
+        let renew_config = tls_config.clone();
+        let renew_acme = startup_config.acme.clone();
+        let renew_state = shared_config.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(renewal_delay).await;
+                renewal_delay = ACME_RENEWAL_INTERVAL;
+                loop {
+                    match obtain_acme_certificate(&renew_acme, &renew_state.acme_challenges).await {
+                        Ok((cert_pem, key_pem)) => {
+                            match renew_config.reload_from_pem(cert_pem.into_bytes(), key_pem.into_bytes()).await {
+                                Ok(()) => println!("Renewed ACME certificate"),
+                                Err(e) => eprintln!("Failed to install renewed ACME certificate: {}", e),
+                            }
+                            break;
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "ACME renewal failed, retrying in {:?}: {}",
+                                ACME_RENEWAL_RETRY_INTERVAL, e
+                            );
+                            tokio::time::sleep(ACME_RENEWAL_RETRY_INTERVAL).await;
+                        }
+                    }
+                }
+            }
+        });
+
+        let handle = axum_server::Handle::new();
+        tokio::spawn(shutdown_handle(handle.clone()));
+
+        axum_server::bind_rustls(addr, tls_config)
+            .handle(handle)
+            .serve(app.into_make_service())
+            .await
+            .unwrap();
+    } else {
+        match (&startup_config.tls_cert_path, &startup_config.tls_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                let tls_config = RustlsConfig::from_pem_file(cert_path, key_path)
+                    .await
+                    .expect("Failed to load TLS cert/key");
+
+                let handle = axum_server::Handle::new();
+                tokio::spawn(shutdown_handle(handle.clone()));
+
+                axum_server::bind_rustls(addr, tls_config)
+                    .handle(handle)
+                    .serve(app.into_make_service())
+                    .await
+                    .unwrap();
+            }
+            _ => {
+                let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+                axum::serve(listener, app)
+                    .with_graceful_shutdown(shutdown_signal())
+                    .await
+                    .unwrap();
+            }
+        }
+    }
 
     println!("Server shut down gracefully");
 }
@@ -120,11 +559,96 @@ async fn health() -> impl IntoResponse {
     )
 }
 
+async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    state.metrics_handle.render()
+}
+
+// Answers the ACME HTTP-01 challenge with the key authorization `obtain_acme_certificate`
+// stashed for this token, if any order is currently in flight.
+async fn acme_challenge(State(state): State<Arc<AppState>>, Path(token): Path<String>) -> impl IntoResponse {
+    match state.acme_challenges.lock().unwrap().get(&token) {
+        Some(key_authorization) => (StatusCode::OK, key_authorization.clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// Pulls the caller's credential out of an `Authorization` header, whether it arrived as
+// `Bearer <token>` or HTTP Basic (`Basic <base64(user:token)>` - only the password half counts).
+fn extract_client_token(header_value: &str) -> Option<String> {
+    if let Some(token) = header_value.strip_prefix("Bearer ") {
+        return Some(token.to_string());
+    }
+    if let Some(encoded) = header_value.strip_prefix("Basic ") {
+        let decoded = STANDARD.decode(encoded).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (_, password) = decoded.split_once(':')?;
+        return Some(password.to_string());
+    }
+    None
+}
+
+// Rejects any request that doesn't present a token from `config.client_tokens` or one minted by
+// `/generate_token`, rather than forwarding it upstream.
+async fn client_auth(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(extract_client_token);
+
+    let authorized = match &presented {
+        Some(token) => {
+            state.config.load().client_tokens.contains(token)
+                || state.generated_tokens.lock().unwrap().contains(token)
+        }
+        None => false,
+    };
+
+    if authorized {
+        next.run(request).await
+    } else {
+        let mut response = StatusCode::UNAUTHORIZED.into_response();
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_static("Bearer"),
+        );
+        response
+    }
+}
+
+#[derive(Serialize)]
+struct GeneratedToken {
+    token: String,
+}
+
+async fn generate_token(State(state): State<Arc<AppState>>) -> Response {
+    let mut generated_tokens = state.generated_tokens.lock().unwrap();
+    if generated_tokens.len() >= MAX_GENERATED_TOKENS {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Generated token limit reached, provision credentials via config instead",
+        )
+            .into_response();
+    }
+
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+
+    generated_tokens.insert(token.clone());
+    drop(generated_tokens);
+
+    (StatusCode::OK, Json(GeneratedToken { token })).into_response()
+}
+
 async fn config(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let config = state.config.load();
     (
         StatusCode::ACCEPTED,
         Json(ApiResponse {
-            data: format!( "backend_url:{}", state.config.backend_url),
+            data: format!( "backend_url:{}", config.backend_url),
             code: 200,
         })
     )
@@ -135,39 +659,186 @@ async fn proxy(
     Path(path): Path<String>,
     request: Request,
 ) -> impl IntoResponse {
-    // Read the Authorization header that middleware added
-    let auth_header = request.headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|h| h.to_str().ok());
+    let config = state.config.load();
+    let route = match_route(&config.routes, &path);
+    let (backend_url, secret_token, header_name) = match route {
+        Some(r) => (r.backend_url.clone(), r.secret_token.clone(), r.header_name.clone()),
+        None => (config.backend_url.clone(), config.secret_token.clone(), None),
+    };
+    drop(config);
 
-    let mut outgoing = state.http_client
-        .get(format!("{}/{}", &state.config.backend_url, &path.trim_end_matches('/')));
+    let method = request.method().clone();
+    let query = request.uri().query().map(|q| q.to_string());
+    let headers = request.headers().clone();
 
-    if let Some(auth) = auth_header {
-        outgoing = outgoing.header("Authorization", auth);
+    let mut url = format!("{}/{}", &backend_url, &path.trim_end_matches('/'));
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(&query);
     }
 
+    let (auth_header_name, auth_value) = match header_name {
+        Some(name) => (name, secret_token),
+        None => (header::AUTHORIZATION.to_string(), format!("Bearer {}", secret_token)),
+    };
+
+    let method = reqwest::Method::from_bytes(method.as_str().as_bytes()).unwrap_or(reqwest::Method::GET);
+    let mut outgoing = state.http_client.request(method, url);
+
+    for (name, value) in headers.iter() {
+        if !should_forward_header(name, &auth_header_name) {
+            continue;
+        }
+        outgoing = outgoing.header(name, value);
+    }
+    outgoing = outgoing.header(&auth_header_name, &auth_value);
+
+    let body_stream = request.into_body().into_data_stream();
+    outgoing = outgoing.body(reqwest::Body::wrap_stream(body_stream));
+
+    metrics::counter!("proxy_requests_total").increment(1);
+    let started_at = std::time::Instant::now();
     let response = outgoing.send().await;
+    metrics::histogram!("proxy_upstream_latency_seconds").record(started_at.elapsed().as_secs_f64());
 
     match response {
         Ok(res) => {
-            let body = res.text().await.unwrap_or_default();
-            (StatusCode::OK, body)
+            let status = StatusCode::from_u16(res.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+            metrics::counter!("proxy_upstream_status_total", "status" => status.as_u16().to_string()).increment(1);
+
+            let mut response_headers = HeaderMap::new();
+            for (name, value) in res.headers().iter() {
+                let Ok(name) = HeaderName::from_bytes(name.as_str().as_bytes()) else { continue };
+                if is_hop_by_hop(&name) {
+                    continue;
+                }
+                if let Ok(value) = HeaderValue::from_bytes(value.as_bytes()) {
+                    response_headers.append(name, value);
+                }
+            }
+
+            let body = Body::from_stream(res.bytes_stream());
+
+            let mut response = Response::new(body);
+            *response.status_mut() = status;
+            *response.headers_mut() = response_headers;
+            response.into_response()
         }
         Err(e) => {
-            (StatusCode::BAD_GATEWAY, format!("Proxy error: {}", e))
+            metrics::counter!("proxy_errors_total").increment(1);
+            (StatusCode::BAD_GATEWAY, format!("Proxy error: {}", e)).into_response()
         }
     }
 }
 
-async fn my_middleware(State(state): State<Arc<AppState>>, mut request: Request, next: Next) -> Result<Response, StatusCode> {
-    let bearer_token = format!("Bearer {}", state.config.secret_token);
-    let header_val = HeaderValue::from_str(&bearer_token)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    request.headers_mut().insert(
-        header::AUTHORIZATION,
-        header_val,
-    );
-    Ok(next.run(request).await)
+    #[test]
+    fn extract_client_token_from_bearer() {
+        assert_eq!(
+            extract_client_token("Bearer abc123"),
+            Some("abc123".to_string())
+        );
+    }
+
+    #[test]
+    fn extract_client_token_from_basic() {
+        // "alice:s3cret" base64-encoded
+        let header = format!("Basic {}", STANDARD.encode("alice:s3cret"));
+        assert_eq!(extract_client_token(&header), Some("s3cret".to_string()));
+    }
+
+    #[test]
+    fn extract_client_token_basic_without_colon_is_rejected() {
+        let header = format!("Basic {}", STANDARD.encode("no-colon-here"));
+        assert_eq!(extract_client_token(&header), None);
+    }
+
+    #[test]
+    fn extract_client_token_rejects_malformed_basic() {
+        assert_eq!(extract_client_token("Basic not-valid-base64!"), None);
+    }
+
+    #[test]
+    fn extract_client_token_rejects_unknown_scheme() {
+        assert_eq!(extract_client_token("Digest abc123"), None);
+        assert_eq!(extract_client_token(""), None);
+    }
+
+    fn route(prefix: &str) -> RouteConfig {
+        RouteConfig {
+            prefix: prefix.to_string(),
+            backend_url: "http://backend".to_string(),
+            secret_token: "token".to_string(),
+            header_name: None,
+        }
+    }
+
+    #[test]
+    fn match_route_accepts_prefix_without_leading_slash() {
+        let routes = vec![route("api")];
+        assert!(match_route(&routes, "api/v1/widgets").is_some());
+    }
+
+    #[test]
+    fn match_route_tolerates_leading_slash_in_config() {
+        let routes = vec![route("/api")];
+        assert!(match_route(&routes, "api/v1/widgets").is_some());
+    }
+
+    #[test]
+    fn match_route_falls_back_to_none_when_no_prefix_matches() {
+        let routes = vec![route("api")];
+        assert!(match_route(&routes, "other/path").is_none());
+    }
+
+    #[test]
+    fn hop_by_hop_headers_are_detected_case_insensitively() {
+        assert!(is_hop_by_hop(&HeaderName::from_static("connection")));
+        assert!(is_hop_by_hop(&HeaderName::from_bytes(b"Transfer-Encoding").unwrap()));
+        assert!(!is_hop_by_hop(&header::AUTHORIZATION));
+        assert!(!is_hop_by_hop(&header::HOST));
+    }
+
+    #[test]
+    fn should_forward_header_drops_inbound_authorization_even_with_custom_header_name() {
+        // A route with a custom header_name injects the upstream secret under that name, not
+        // Authorization - the client's own Authorization must still be dropped, not leaked.
+        assert!(!should_forward_header(&header::AUTHORIZATION, "X-Upstream-Token"));
+        assert!(!should_forward_header(&header::HOST, "X-Upstream-Token"));
+        assert!(!should_forward_header(
+            &HeaderName::from_static("x-upstream-token"),
+            "X-Upstream-Token"
+        ));
+        assert!(should_forward_header(
+            &HeaderName::from_static("x-request-id"),
+            "X-Upstream-Token"
+        ));
+    }
+
+    #[test]
+    fn acme_renewal_delay_is_full_interval_for_a_freshly_issued_cert() {
+        let delay = acme_renewal_delay(Some(SystemTime::now()));
+        assert!(ACME_RENEWAL_INTERVAL - delay < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn acme_renewal_delay_accounts_for_cert_age() {
+        let issued_at = SystemTime::now() - Duration::from_secs(60 * 60 * 24 * 50);
+        let delay = acme_renewal_delay(Some(issued_at));
+        assert!(delay <= Duration::from_secs(60 * 60 * 24 * 10));
+    }
+
+    #[test]
+    fn acme_renewal_delay_is_due_immediately_once_past_the_interval() {
+        let issued_at = SystemTime::now() - ACME_RENEWAL_INTERVAL - Duration::from_secs(60);
+        assert_eq!(acme_renewal_delay(Some(issued_at)), Duration::ZERO);
+    }
+
+    #[test]
+    fn acme_renewal_delay_is_due_immediately_with_unknown_issuance() {
+        assert_eq!(acme_renewal_delay(None), Duration::ZERO);
+    }
 }